@@ -3,12 +3,33 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use std::collections::HashSet;
+use std::net::IpAddr;
+use std::str::FromStr;
 
-use miette::{IntoDiagnostic, Result};
-use x509_cert::{ext::pkix::certpolicy::PolicyInformation, spki::ObjectIdentifier};
+use miette::{IntoDiagnostic, Result, Severity};
+use x509_cert::{
+    der::{
+        asn1::{Any, Ia5String, OctetString},
+        Tag,
+    },
+    ext::pkix::{
+        certpolicy::{DisplayText, NoticeReference, PolicyInformation, PolicyQualifierInfo, UserNotice},
+        constraints::name::{GeneralSubtree as PkixGeneralSubtree, NameConstraints},
+        name::{DistributionPointName, GeneralName, OtherName},
+        AccessDescription, AuthorityInfoAccessSyntax, CrlDistributionPoints, DistributionPoint,
+    },
+    name::{RdnSequence, RelativeDistinguishedName},
+    spki::ObjectIdentifier,
+};
 
 #[derive(knuffel::Decode, Debug)]
 pub struct Document {
+    /// Policy OIDs (or `any-policy`) the relying party trusts at the start of path validation,
+    /// used as the initial-policy-set input to the RFC 5280 §6.1 policy-tree algorithm. Empty
+    /// means anyPolicy, i.e. unconstrained.
+    #[knuffel(child, unwrap(arguments), default)]
+    pub initial_policy_set: Vec<String>,
+
     #[knuffel(children(name = "key-pair"))]
     pub key_pairs: Vec<KeyPair>,
 
@@ -125,6 +146,13 @@ pub enum X509Extensions {
     AuthorityKeyIdentifier(AuthorityKeyIdentifierExtension),
     ExtendedKeyUsage(ExtendedKeyUsageExtension),
     CertificatePolicies(CertificatePoliciesExtension),
+    SubjectAltName(SubjectAltNameExtension),
+    NameConstraints(NameConstraintsExtension),
+    CrlDistributionPoints(CrlDistributionPointsExtension),
+    AuthorityInfoAccess(AuthorityInfoAccessExtension),
+    PolicyConstraints(PolicyConstraintsExtension),
+    IpResources(IpResourcesExtension),
+    AsResources(AsResourcesExtension),
 }
 
 #[derive(knuffel::Decode, Debug)]
@@ -217,42 +245,619 @@ pub struct AuthorityKeyIdentifierExtension {
     pub issuer: bool,
 }
 
+#[derive(knuffel::Decode, Debug)]
+pub struct SubjectAltNameExtension {
+    #[knuffel(property)]
+    pub critical: bool,
+
+    #[knuffel(children)]
+    pub names: Vec<GeneralNameEntry>,
+}
+
+/// The `GeneralNameEntry` enum represents the set of KDL nodes that map to `x509_cert`'s
+/// `GeneralName` choice, used in `SubjectAltName`. `NameConstraints` subtrees use the narrower
+/// `GeneralSubtree` enum instead.
+#[derive(knuffel::Decode, Debug)]
+pub enum GeneralNameEntry {
+    DnsName(#[knuffel(argument)] String),
+    IpAddress(#[knuffel(argument)] String),
+    Uri(#[knuffel(argument)] String),
+    Rfc822Name(#[knuffel(argument)] String),
+    DirectoryName(#[knuffel(children)] Vec<EntityNameComponent>),
+    OtherName(OtherNameEntry),
+}
+
+#[derive(knuffel::Decode, Debug)]
+pub struct OtherNameEntry {
+    #[knuffel(argument)]
+    pub oid: String,
+    #[knuffel(argument)]
+    pub value: String,
+}
+
+/// Build an RDN sequence from the same `EntityNameComponent` list used for entity base DNs, for
+/// use in GeneralName's `directory-name` choice.
+fn rdn_sequence_from_components(components: &[EntityNameComponent]) -> Result<RdnSequence> {
+    let mut rdns = Vec::with_capacity(components.len());
+    for component in components {
+        let (oid, value) = match component {
+            EntityNameComponent::CountryName(v) => ("2.5.4.6", v),
+            EntityNameComponent::StateOrProvinceName(v) => ("2.5.4.8", v),
+            EntityNameComponent::LocalityName(v) => ("2.5.4.7", v),
+            EntityNameComponent::OrganizationName(v) => ("2.5.4.10", v),
+            EntityNameComponent::OrganizationalUnitName(v) => ("2.5.4.11", v),
+        };
+        let rdn_str = format!("{}={}", oid, value);
+        rdns.push(RelativeDistinguishedName::from_str(&rdn_str).into_diagnostic()?);
+    }
+
+    Ok(RdnSequence(rdns))
+}
+
+impl TryFrom<&GeneralNameEntry> for GeneralName {
+    type Error = miette::Error;
+
+    /// Map a `GeneralNameEntry` to the `GeneralName` choice it represents, for inclusion in a
+    /// `SubjectAltName` extension.
+    fn try_from(value: &GeneralNameEntry) -> Result<Self> {
+        Ok(match value {
+            GeneralNameEntry::DnsName(s) => {
+                GeneralName::DnsName(Ia5String::new(s).into_diagnostic()?)
+            }
+            GeneralNameEntry::IpAddress(s) => {
+                let addr: IpAddr = s
+                    .parse()
+                    .into_diagnostic()
+                    .map_err(|e| miette::miette!("invalid ip-address \"{}\": {}", s, e))?;
+                let octets = match addr {
+                    IpAddr::V4(v4) => v4.octets().to_vec(),
+                    IpAddr::V6(v6) => v6.octets().to_vec(),
+                };
+                GeneralName::IpAddress(OctetString::new(octets).into_diagnostic()?)
+            }
+            GeneralNameEntry::Uri(s) => GeneralName::Uri(Ia5String::new(s).into_diagnostic()?),
+            GeneralNameEntry::Rfc822Name(s) => {
+                GeneralName::Rfc822Name(Ia5String::new(s).into_diagnostic()?)
+            }
+            GeneralNameEntry::DirectoryName(components) => {
+                GeneralName::DirectoryName(rdn_sequence_from_components(components)?)
+            }
+            GeneralNameEntry::OtherName(other) => {
+                let type_id = ObjectIdentifier::new(&other.oid).into_diagnostic()?;
+                let value = Any::new(Tag::Utf8String, other.value.as_bytes())
+                    .into_diagnostic()
+                    .map_err(|e: miette::Error| {
+                        miette::miette!("invalid other-name value \"{}\": {}", other.value, e)
+                    })?;
+                GeneralName::OtherName(OtherName { type_id, value })
+            }
+        })
+    }
+}
+
+#[derive(knuffel::Decode, Debug)]
+pub struct NameConstraintsExtension {
+    #[knuffel(property)]
+    pub critical: bool,
+
+    #[knuffel(child, unwrap(children), default)]
+    pub permitted: Vec<GeneralSubtree>,
+
+    #[knuffel(child, unwrap(children), default)]
+    pub excluded: Vec<GeneralSubtree>,
+}
+
+/// The `GeneralSubtree` enum represents the set of KDL nodes that map to the subset of
+/// `GeneralName` choices that RFC 5280 §4.2.1.10 permits in a `NameConstraints` subtree.
+#[derive(knuffel::Decode, Debug)]
+pub enum GeneralSubtree {
+    DnsName(#[knuffel(argument)] String),
+    IpAddress(#[knuffel(argument)] String),
+    DirectoryName(#[knuffel(children)] Vec<EntityNameComponent>),
+    Rfc822Name(#[knuffel(argument)] String),
+}
+
+/// Split a `addr/prefix-len` string into its address and prefix length, as used by the
+/// `ip-address` choice of a `GeneralSubtree`.
+fn parse_cidr(s: &str) -> Result<(IpAddr, u8)> {
+    let (addr, prefix_len) = s
+        .split_once('/')
+        .ok_or_else(|| miette::miette!("ip-address subtree \"{}\" is missing a /prefix-len", s))?;
+    let addr: IpAddr = addr
+        .parse()
+        .into_diagnostic()
+        .map_err(|e| miette::miette!("invalid ip-address subtree \"{}\": {}", s, e))?;
+    let prefix_len: u8 = prefix_len
+        .parse()
+        .into_diagnostic()
+        .map_err(|e| miette::miette!("invalid prefix length in \"{}\": {}", s, e))?;
+
+    let max_prefix_len = match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+    if prefix_len > max_prefix_len {
+        miette::bail!(
+            "prefix length in \"{}\" is {}, exceeding the {}-bit maximum for {}",
+            s,
+            prefix_len,
+            max_prefix_len,
+            if addr.is_ipv4() { "IPv4" } else { "IPv6" }
+        )
+    }
+
+    Ok((addr, prefix_len))
+}
+
+/// Encode an `addr/prefix-len` subtree as the address-and-subnet-mask octet string RFC 5280
+/// §4.2.1.10 requires for the `iPAddress` choice.
+fn cidr_to_octets(addr: IpAddr, prefix_len: u8) -> Vec<u8> {
+    match addr {
+        IpAddr::V4(v4) => {
+            let mask = if prefix_len == 0 {
+                0u32
+            } else {
+                u32::MAX << (32 - prefix_len)
+            };
+            let mut octets = v4.octets().to_vec();
+            octets.extend_from_slice(&mask.to_be_bytes());
+            octets
+        }
+        IpAddr::V6(v6) => {
+            let mask: u128 = if prefix_len == 0 {
+                0u128
+            } else {
+                u128::MAX << (128 - prefix_len)
+            };
+            let mut octets = v6.octets().to_vec();
+            octets.extend_from_slice(&mask.to_be_bytes());
+            octets
+        }
+    }
+}
+
+impl TryFrom<&GeneralSubtree> for PkixGeneralSubtree {
+    type Error = miette::Error;
+
+    /// Map a `GeneralSubtree` to the base `GeneralName` plus the default `minimum`/`maximum`
+    /// fields `x509_cert`'s `GeneralSubtree` expects; `pki-playground` only ever emits the base.
+    fn try_from(value: &GeneralSubtree) -> Result<Self> {
+        let base = match value {
+            GeneralSubtree::DnsName(s) => GeneralName::DnsName(Ia5String::new(s).into_diagnostic()?),
+            GeneralSubtree::IpAddress(s) => {
+                let (addr, prefix_len) = parse_cidr(s)?;
+                GeneralName::IpAddress(
+                    OctetString::new(cidr_to_octets(addr, prefix_len)).into_diagnostic()?,
+                )
+            }
+            GeneralSubtree::DirectoryName(components) => {
+                GeneralName::DirectoryName(rdn_sequence_from_components(components)?)
+            }
+            GeneralSubtree::Rfc822Name(s) => {
+                GeneralName::Rfc822Name(Ia5String::new(s).into_diagnostic()?)
+            }
+        };
+
+        Ok(PkixGeneralSubtree {
+            base,
+            minimum: 0,
+            maximum: None,
+        })
+    }
+}
+
+impl TryFrom<&NameConstraintsExtension> for NameConstraints {
+    type Error = miette::Error;
+
+    fn try_from(value: &NameConstraintsExtension) -> Result<Self> {
+        let permitted_subtrees = if value.permitted.is_empty() {
+            None
+        } else {
+            Some(
+                value
+                    .permitted
+                    .iter()
+                    .map(PkixGeneralSubtree::try_from)
+                    .collect::<Result<Vec<_>>>()?,
+            )
+        };
+
+        let excluded_subtrees = if value.excluded.is_empty() {
+            None
+        } else {
+            Some(
+                value
+                    .excluded
+                    .iter()
+                    .map(PkixGeneralSubtree::try_from)
+                    .collect::<Result<Vec<_>>>()?,
+            )
+        };
+
+        Ok(NameConstraints {
+            permitted_subtrees,
+            excluded_subtrees,
+        })
+    }
+}
+
+/// Does `candidate` fall within the DNS name subtree rooted at `constraint`, per RFC 5280
+/// §4.2.1.10 (equal to, or a subdomain of, the constraint label)?
+fn dns_name_in_subtree(candidate: &str, constraint: &str) -> bool {
+    let candidate = candidate.trim_end_matches('.').to_ascii_lowercase();
+    let constraint = constraint.trim_end_matches('.').to_ascii_lowercase();
+    candidate == constraint || candidate.ends_with(&format!(".{constraint}"))
+}
+
+/// Does `candidate` fall within the `addr/prefix-len` subtree?
+fn ip_in_subtree(candidate: &IpAddr, cidr: &str) -> Result<bool> {
+    let (base, prefix_len) = parse_cidr(cidr)?;
+    Ok(match (candidate, base) {
+        (IpAddr::V4(candidate), IpAddr::V4(base)) => {
+            let mask = if prefix_len == 0 {
+                0u32
+            } else {
+                u32::MAX << (32 - prefix_len)
+            };
+            u32::from(*candidate) & mask == u32::from(base) & mask
+        }
+        (IpAddr::V6(candidate), IpAddr::V6(base)) => {
+            let mask: u128 = if prefix_len == 0 {
+                0u128
+            } else {
+                u128::MAX << (128 - prefix_len)
+            };
+            u128::from(*candidate) & mask == u128::from(base) & mask
+        }
+        _ => false,
+    })
+}
+
+/// Check that `subject`'s dNSName/IP SANs are contained by `issuer`'s `NameConstraints`
+/// extension, if it has one. Bails with the name of the offending certificate when a SAN falls
+/// outside a permitted subtree or inside an excluded one.
+fn check_name_constraints(subject: &Certificate, issuer: &Certificate) -> Result<()> {
+    let Some(X509Extensions::NameConstraints(nc)) = issuer
+        .extensions
+        .iter()
+        .find(|e| matches!(e, X509Extensions::NameConstraints(_)))
+    else {
+        return Ok(());
+    };
+
+    let Some(X509Extensions::SubjectAltName(san)) = subject
+        .extensions
+        .iter()
+        .find(|e| matches!(e, X509Extensions::SubjectAltName(_)))
+    else {
+        return Ok(());
+    };
+
+    for name in &san.names {
+        match name {
+            GeneralNameEntry::DnsName(dns) => {
+                if !nc.excluded.iter().all(|subtree| match subtree {
+                    GeneralSubtree::DnsName(c) => !dns_name_in_subtree(dns, c),
+                    _ => true,
+                }) {
+                    miette::bail!(
+                        "certificate \"{}\" dNSName \"{}\" falls within excluded subtree of issuer \"{}\"",
+                        subject.name,
+                        dns,
+                        issuer.name
+                    )
+                }
+
+                let permitted_dns: Vec<&String> = nc
+                    .permitted
+                    .iter()
+                    .filter_map(|subtree| match subtree {
+                        GeneralSubtree::DnsName(c) => Some(c),
+                        _ => None,
+                    })
+                    .collect();
+                if !permitted_dns.is_empty()
+                    && !permitted_dns.iter().any(|c| dns_name_in_subtree(dns, c))
+                {
+                    miette::bail!(
+                        "certificate \"{}\" dNSName \"{}\" is not within any permitted subtree of issuer \"{}\"",
+                        subject.name,
+                        dns,
+                        issuer.name
+                    )
+                }
+            }
+            GeneralNameEntry::IpAddress(ip) => {
+                let candidate: IpAddr = ip.parse().into_diagnostic().map_err(|e| {
+                    miette::miette!("invalid ip-address \"{}\" on certificate \"{}\": {}", ip, subject.name, e)
+                })?;
+
+                for subtree in &nc.excluded {
+                    if let GeneralSubtree::IpAddress(c) = subtree {
+                        if ip_in_subtree(&candidate, c)? {
+                            miette::bail!(
+                                "certificate \"{}\" IP address \"{}\" falls within excluded subtree of issuer \"{}\"",
+                                subject.name,
+                                ip,
+                                issuer.name
+                            )
+                        }
+                    }
+                }
+
+                let permitted_ip: Vec<&String> = nc
+                    .permitted
+                    .iter()
+                    .filter_map(|subtree| match subtree {
+                        GeneralSubtree::IpAddress(c) => Some(c),
+                        _ => None,
+                    })
+                    .collect();
+                if !permitted_ip.is_empty() {
+                    let mut contained = false;
+                    for c in &permitted_ip {
+                        if ip_in_subtree(&candidate, c)? {
+                            contained = true;
+                            break;
+                        }
+                    }
+                    if !contained {
+                        miette::bail!(
+                            "certificate \"{}\" IP address \"{}\" is not within any permitted subtree of issuer \"{}\"",
+                            subject.name,
+                            ip,
+                            issuer.name
+                        )
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(knuffel::Decode, Debug)]
+pub struct CrlDistributionPointsExtension {
+    #[knuffel(property)]
+    pub critical: bool,
+
+    #[knuffel(children(name = "distribution-point"))]
+    pub distribution_points: Vec<DistributionPointEntry>,
+}
+
+#[derive(knuffel::Decode, Debug)]
+pub struct DistributionPointEntry {
+    #[knuffel(child, unwrap(argument))]
+    pub full_name: String,
+}
+
+impl TryFrom<&CrlDistributionPointsExtension> for CrlDistributionPoints {
+    type Error = miette::Error;
+
+    fn try_from(value: &CrlDistributionPointsExtension) -> Result<Self> {
+        value
+            .distribution_points
+            .iter()
+            .map(|dp| {
+                let uri = Ia5String::new(&dp.full_name).into_diagnostic()?;
+                Ok(DistributionPoint {
+                    distribution_point: Some(DistributionPointName::FullName(vec![
+                        GeneralName::Uri(uri),
+                    ])),
+                    reasons: None,
+                    crl_issuer: None,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(knuffel::Decode, Debug)]
+pub struct AuthorityInfoAccessExtension {
+    #[knuffel(property)]
+    pub critical: bool,
+
+    #[knuffel(children(name = "ocsp"), unwrap(argument))]
+    pub ocsp: Vec<String>,
+
+    #[knuffel(children(name = "ca-issuers"), unwrap(argument))]
+    pub ca_issuers: Vec<String>,
+}
+
+impl TryFrom<&AuthorityInfoAccessExtension> for AuthorityInfoAccessSyntax {
+    type Error = miette::Error;
+
+    fn try_from(value: &AuthorityInfoAccessExtension) -> Result<Self> {
+        let ocsp_oid = ObjectIdentifier::new("1.3.6.1.5.5.7.48.1").into_diagnostic()?;
+        let ca_issuers_oid = ObjectIdentifier::new("1.3.6.1.5.5.7.48.2").into_diagnostic()?;
+
+        let ocsp = value.ocsp.iter().map(|uri| (ocsp_oid, uri));
+        let ca_issuers = value.ca_issuers.iter().map(|uri| (ca_issuers_oid, uri));
+
+        ocsp.chain(ca_issuers)
+            .map(|(access_method, uri)| {
+                Ok(AccessDescription {
+                    access_method,
+                    access_location: GeneralName::Uri(Ia5String::new(uri).into_diagnostic()?),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The `PolicyQualifier` enum represents the set of KDL nodes that can appear under a
+/// `CertificatePolicy` node to attach RFC 5280 §4.2.1.4 policy qualifiers.
+#[derive(knuffel::Decode, Debug)]
+pub enum PolicyQualifier {
+    /// `cps` child holding the URI of the CA's Certification Practice Statement.
+    Cps(#[knuffel(argument)] String),
+    /// `user-notice` child describing a notice to display to a relying party.
+    UserNotice(UserNoticeQualifier),
+}
+
+#[derive(knuffel::Decode, Debug)]
+pub struct UserNoticeQualifier {
+    #[knuffel(property)]
+    pub organization: Option<String>,
+
+    /// Comma-separated list of notice numbers, referenced against `organization`.
+    #[knuffel(property(name = "notice-numbers"))]
+    pub notice_numbers: Option<String>,
+
+    #[knuffel(child, unwrap(argument))]
+    pub explicit_text: Option<String>,
+}
+
 /// The `CertificatePolicy` enum represents the set of KDL nodes that `pki-playground` can map to
-/// OIDs. Configs may also provide OIDs in their string forms using the `oid` node.
+/// OIDs. Configs may also provide OIDs in their string forms using the `oid` node. Each variant
+/// may carry a list of `PolicyQualifier` children (`cps`, `user-notice`).
 #[derive(knuffel::Decode, Debug)]
 pub enum CertificatePolicy {
     /// Initial attestation policy OID from [DICE Certificate
     /// Profiles](https://trustedcomputinggroup.org/resource/dice-certificate-profiles/) §5.1.5.3
-    TcgDiceKpAttestInit,
+    TcgDiceKpAttestInit {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// Local attestation policy OID from [DICE Certificate
     /// Profiles](https://trustedcomputinggroup.org/resource/dice-certificate-profiles/) §5.1.5.4
-    TcgDiceKpAttestLoc,
+    TcgDiceKpAttestLoc {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// Initial assertion policy OID from [DICE Certificate
     /// Profiles](https://trustedcomputinggroup.org/resource/dice-certificate-profiles/) §5.1.5.5
-    TcgDiceKpAssertInit,
+    TcgDiceKpAssertInit {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// Local assertion policy OID from [DICE Certificate
     /// Profiles](https://trustedcomputinggroup.org/resource/dice-certificate-profiles/) §5.1.5.6
-    TcgDiceKpAssertLoc,
+    TcgDiceKpAssertLoc {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// Embedded certificate authority (ECA) policy OID from [DICE Certificate
     /// Profiles](https://trustedcomputinggroup.org/resource/dice-certificate-profiles/) §5.1.5.7
-    TcgDiceKpEca,
+    TcgDiceKpEca {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// Initial identity policy OID from [DICE Certificate
     /// Profiles](https://trustedcomputinggroup.org/resource/dice-certificate-profiles/) §5.1.5.1
-    TcgDiceKpIdentityInit,
+    TcgDiceKpIdentityInit {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// Local identity policy OID from [DICE Certificate
     /// Profiles](https://trustedcomputinggroup.org/resource/dice-certificate-profiles/) §5.1.5.2
-    TcgDiceKpIdentityLoc,
+    TcgDiceKpIdentityLoc {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// Platform identity policy from [OANA x.509 certificate policy
     /// terms](https://github.com/oxidecomputer/oana#asn1-object-identifiers)
-    OanaPlatformIdentity,
+    OanaPlatformIdentity {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// RoT code signing development policy from [OANA x.509 certificate policy
     /// terms](https://github.com/oxidecomputer/oana#asn1-object-identifiers)
-    OanaRotCodeSigningDevelopment,
+    OanaRotCodeSigningDevelopment {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// RoT code signing release policy from [OANA x.509 certificate policy
     /// terms](https://github.com/oxidecomputer/oana#asn1-object-identifiers)
-    OanaRotCodeSigningRelease,
+    OanaRotCodeSigningRelease {
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
     /// `oid` node taking an OID string argument
-    Oid(#[knuffel(argument)] String),
+    Oid {
+        #[knuffel(argument)]
+        oid: String,
+        #[knuffel(children)]
+        qualifiers: Vec<PolicyQualifier>,
+    },
+}
+
+impl CertificatePolicy {
+    fn qualifiers(&self) -> &[PolicyQualifier] {
+        match self {
+            CertificatePolicy::TcgDiceKpAttestInit { qualifiers }
+            | CertificatePolicy::TcgDiceKpAttestLoc { qualifiers }
+            | CertificatePolicy::TcgDiceKpAssertInit { qualifiers }
+            | CertificatePolicy::TcgDiceKpAssertLoc { qualifiers }
+            | CertificatePolicy::TcgDiceKpEca { qualifiers }
+            | CertificatePolicy::TcgDiceKpIdentityInit { qualifiers }
+            | CertificatePolicy::TcgDiceKpIdentityLoc { qualifiers }
+            | CertificatePolicy::OanaPlatformIdentity { qualifiers }
+            | CertificatePolicy::OanaRotCodeSigningDevelopment { qualifiers }
+            | CertificatePolicy::OanaRotCodeSigningRelease { qualifiers }
+            | CertificatePolicy::Oid { qualifiers, .. } => qualifiers,
+        }
+    }
+}
+
+/// RFC 5280 §4.2.1.4 limits `explicitText` to 200 characters, but some CAs emit longer strings
+/// in the wild; we accept them rather than silently truncating and let `load_and_validate` warn.
+const EXPLICIT_TEXT_MAX_LEN: usize = 200;
+
+fn policy_qualifier_info(qualifier: &PolicyQualifier) -> Result<PolicyQualifierInfo> {
+    match qualifier {
+        PolicyQualifier::Cps(uri) => Ok(PolicyQualifierInfo {
+            policy_qualifier_id: ObjectIdentifier::new("1.3.6.1.5.5.7.2.1").into_diagnostic()?,
+            qualifier: Some(
+                Any::new(Tag::Ia5String, Ia5String::new(uri).into_diagnostic()?.as_bytes())
+                    .into_diagnostic()?,
+            ),
+        }),
+        PolicyQualifier::UserNotice(notice) => {
+            let notice_numbers = notice
+                .notice_numbers
+                .as_deref()
+                .map(|s| {
+                    s.split(',')
+                        .map(|n| n.trim().parse::<i32>().into_diagnostic())
+                        .collect::<Result<Vec<_>>>()
+                })
+                .transpose()?
+                .unwrap_or_default();
+
+            let user_notice = UserNotice {
+                notice_ref: notice
+                    .organization
+                    .as_ref()
+                    .map(|organization| {
+                        Ok::<_, miette::Error>(NoticeReference {
+                            organization: DisplayText::Utf8String(
+                                String::from(organization).try_into().into_diagnostic()?,
+                            ),
+                            notice_numbers,
+                        })
+                    })
+                    .transpose()?,
+                explicit_text: notice
+                    .explicit_text
+                    .as_ref()
+                    .map(|text| {
+                        Ok::<_, miette::Error>(DisplayText::Utf8String(
+                            text.clone().try_into().into_diagnostic()?,
+                        ))
+                    })
+                    .transpose()?,
+            };
+
+            Ok(PolicyQualifierInfo {
+                policy_qualifier_id: ObjectIdentifier::new("1.3.6.1.5.5.7.2.2")
+                    .into_diagnostic()?,
+                qualifier: Some(Any::encode_from(&user_notice).into_diagnostic()?),
+            })
+        }
+    }
 }
 
 impl TryFrom<&CertificatePolicy> for PolicyInformation {
@@ -262,42 +867,54 @@ impl TryFrom<&CertificatePolicy> for PolicyInformation {
     /// required as part of our conversion from the KDL to the DER certificate encoding.
     fn try_from(value: &CertificatePolicy) -> Result<Self> {
         let oid = match value {
-            CertificatePolicy::TcgDiceKpIdentityInit => {
+            CertificatePolicy::TcgDiceKpIdentityInit { .. } => {
                 ObjectIdentifier::new("2.23.133.5.4.100.6").into_diagnostic()?
             }
-            CertificatePolicy::TcgDiceKpIdentityLoc => {
+            CertificatePolicy::TcgDiceKpIdentityLoc { .. } => {
                 ObjectIdentifier::new("2.23.133.5.4.100.7").into_diagnostic()?
             }
-            CertificatePolicy::TcgDiceKpAttestInit => {
+            CertificatePolicy::TcgDiceKpAttestInit { .. } => {
                 ObjectIdentifier::new("2.23.133.5.4.100.8").into_diagnostic()?
             }
-            CertificatePolicy::TcgDiceKpAttestLoc => {
+            CertificatePolicy::TcgDiceKpAttestLoc { .. } => {
                 ObjectIdentifier::new("2.23.133.5.4.100.9").into_diagnostic()?
             }
-            CertificatePolicy::TcgDiceKpAssertInit => {
+            CertificatePolicy::TcgDiceKpAssertInit { .. } => {
                 ObjectIdentifier::new("2.23.133.5.4.100.10").into_diagnostic()?
             }
-            CertificatePolicy::TcgDiceKpAssertLoc => {
+            CertificatePolicy::TcgDiceKpAssertLoc { .. } => {
                 ObjectIdentifier::new("2.23.133.5.4.100.11").into_diagnostic()?
             }
-            CertificatePolicy::TcgDiceKpEca => {
+            CertificatePolicy::TcgDiceKpEca { .. } => {
                 ObjectIdentifier::new("2.23.133.5.4.100.12").into_diagnostic()?
             }
-            CertificatePolicy::OanaRotCodeSigningRelease => {
+            CertificatePolicy::OanaRotCodeSigningRelease { .. } => {
                 ObjectIdentifier::new("1.3.6.1.4.1.57551.1.1").into_diagnostic()?
             }
-            CertificatePolicy::OanaRotCodeSigningDevelopment => {
+            CertificatePolicy::OanaRotCodeSigningDevelopment { .. } => {
                 ObjectIdentifier::new("1.3.6.1.4.1.57551.1.2").into_diagnostic()?
             }
-            CertificatePolicy::OanaPlatformIdentity => {
+            CertificatePolicy::OanaPlatformIdentity { .. } => {
                 ObjectIdentifier::new("1.3.6.1.4.1.57551.1.3").into_diagnostic()?
             }
-            CertificatePolicy::Oid(s) => ObjectIdentifier::new(s).into_diagnostic()?,
+            CertificatePolicy::Oid { oid, .. } => ObjectIdentifier::new(oid).into_diagnostic()?,
+        };
+
+        let qualifiers = value.qualifiers();
+        let policy_qualifiers = if qualifiers.is_empty() {
+            None
+        } else {
+            Some(
+                qualifiers
+                    .iter()
+                    .map(policy_qualifier_info)
+                    .collect::<Result<Vec<_>>>()?,
+            )
         };
 
         Ok(PolicyInformation {
             policy_identifier: oid,
-            policy_qualifiers: None,
+            policy_qualifiers,
         })
     }
 }
@@ -311,6 +928,105 @@ pub struct CertificatePoliciesExtension {
     pub policies: Vec<CertificatePolicy>,
 }
 
+#[derive(knuffel::Decode, Debug)]
+pub struct PolicyConstraintsExtension {
+    #[knuffel(property)]
+    pub critical: bool,
+
+    #[knuffel(property(name = "require-explicit-policy"))]
+    pub require_explicit_policy: Option<u32>,
+
+    #[knuffel(property(name = "inhibit-policy-mapping"))]
+    pub inhibit_policy_mapping: Option<u32>,
+}
+
+impl TryFrom<&PolicyConstraintsExtension> for x509_cert::ext::Extension {
+    type Error = miette::Error;
+
+    /// RFC 5280 §4.2.1.11 `id-ce-policyConstraints`. `x509_cert` does not ship a
+    /// `PolicyConstraints` type, so this is DER-encoded by hand and wrapped in the generic
+    /// `Extension` envelope, the same approach the RFC 3779 resource extensions use below.
+    /// `requireExplicitPolicy`/`inhibitPolicyMapping` are `[0]`/`[1] IMPLICIT` `INTEGER`s.
+    fn try_from(value: &PolicyConstraintsExtension) -> Result<Self> {
+        let mut content = Vec::new();
+        if let Some(require) = value.require_explicit_policy {
+            der_tlv(&mut content, 0x80, &der_integer_content(require));
+        }
+        if let Some(inhibit) = value.inhibit_policy_mapping {
+            der_tlv(&mut content, 0x81, &der_integer_content(inhibit));
+        }
+
+        let mut policy_constraints = Vec::new();
+        der_tlv(&mut policy_constraints, 0x30, &content);
+
+        Ok(x509_cert::ext::Extension {
+            extn_id: ObjectIdentifier::new("2.5.29.36").into_diagnostic()?,
+            critical: value.critical,
+            extn_value: OctetString::new(policy_constraints).into_diagnostic()?,
+        })
+    }
+}
+
+/// RFC 3779 `IPAddrBlock` extension: grants the certificate's subject the right to issue and use
+/// the listed IPv4/IPv6 resources, or to inherit whatever its issuer holds.
+#[derive(knuffel::Decode, Debug)]
+pub struct IpResourcesExtension {
+    #[knuffel(property)]
+    pub critical: bool,
+
+    /// Inherit the issuer's entire IP address resource set rather than listing explicit blocks.
+    #[knuffel(child)]
+    pub inherit: bool,
+
+    #[knuffel(children(name = "ipv4"))]
+    pub ipv4: Vec<IpResourceEntry>,
+
+    #[knuffel(children(name = "ipv6"))]
+    pub ipv6: Vec<IpResourceEntry>,
+}
+
+/// A single `ipv4`/`ipv6` child: either a CIDR prefix argument (`"10.0.0.0/8"`) or a `min`/`max`
+/// address range.
+#[derive(knuffel::Decode, Debug)]
+pub struct IpResourceEntry {
+    #[knuffel(argument)]
+    pub prefix: Option<String>,
+
+    #[knuffel(property)]
+    pub min: Option<String>,
+
+    #[knuffel(property)]
+    pub max: Option<String>,
+}
+
+/// RFC 3779 `ASIdentifiers` extension: grants the certificate's subject the right to use the
+/// listed autonomous system numbers, or to inherit whatever its issuer holds.
+#[derive(knuffel::Decode, Debug)]
+pub struct AsResourcesExtension {
+    #[knuffel(property)]
+    pub critical: bool,
+
+    /// Inherit the issuer's entire AS number resource set rather than listing explicit values.
+    #[knuffel(child)]
+    pub inherit: bool,
+
+    #[knuffel(children(name = "asn"))]
+    pub asn: Vec<AsResourceEntry>,
+}
+
+/// A single `asn` child: either a bare AS number argument or a `min`/`max` range.
+#[derive(knuffel::Decode, Debug)]
+pub struct AsResourceEntry {
+    #[knuffel(argument)]
+    pub value: Option<u32>,
+
+    #[knuffel(property)]
+    pub min: Option<u32>,
+
+    #[knuffel(property)]
+    pub max: Option<u32>,
+}
+
 pub fn load_and_validate(path: &std::path::Path) -> Result<Document> {
     let in_kdl = std::fs::read_to_string(path).into_diagnostic()?;
     let doc: Document = knuffel::parse(&path.to_string_lossy(), &in_kdl)?;
@@ -401,7 +1117,915 @@ pub fn load_and_validate(path: &std::path::Path) -> Result<Document> {
                 cert.issuer_key
             )
         }
+
+        for ext in &cert.extensions {
+            match ext {
+                X509Extensions::SubjectAltName(san) if san.names.is_empty() => {
+                    miette::bail!(
+                        "certificate \"{}\" has a subjectAltName extension with no names",
+                        cert.name
+                    )
+                }
+                X509Extensions::CrlDistributionPoints(cdp) if cdp.distribution_points.is_empty() => {
+                    miette::bail!(
+                        "certificate \"{}\" has a cRLDistributionPoints extension with no distribution points",
+                        cert.name
+                    )
+                }
+                X509Extensions::AuthorityInfoAccess(aia)
+                    if aia.ocsp.is_empty() && aia.ca_issuers.is_empty() =>
+                {
+                    miette::bail!(
+                        "certificate \"{}\" has an authorityInfoAccess extension with no access descriptions",
+                        cert.name
+                    )
+                }
+                X509Extensions::IpResources(ip) if ip.inherit && (!ip.ipv4.is_empty() || !ip.ipv6.is_empty()) => {
+                    miette::bail!(
+                        "certificate \"{}\" has an ipResources extension with both \"inherit\" and explicit blocks",
+                        cert.name
+                    )
+                }
+                X509Extensions::IpResources(ip) if !ip.inherit && ip.ipv4.is_empty() && ip.ipv6.is_empty() => {
+                    miette::bail!(
+                        "certificate \"{}\" has an ipResources extension with neither \"inherit\" nor any blocks",
+                        cert.name
+                    )
+                }
+                X509Extensions::AsResources(asres) if asres.inherit && !asres.asn.is_empty() => {
+                    miette::bail!(
+                        "certificate \"{}\" has an asResources extension with both \"inherit\" and explicit AS numbers",
+                        cert.name
+                    )
+                }
+                X509Extensions::AsResources(asres) if !asres.inherit && asres.asn.is_empty() => {
+                    miette::bail!(
+                        "certificate \"{}\" has an asResources extension with neither \"inherit\" nor any AS numbers",
+                        cert.name
+                    )
+                }
+                X509Extensions::CertificatePolicies(policies) => {
+                    for policy in &policies.policies {
+                        for qualifier in policy.qualifiers() {
+                            if let PolicyQualifier::UserNotice(notice) = qualifier {
+                                if let Some(text) = &notice.explicit_text {
+                                    if text.chars().count() > EXPLICIT_TEXT_MAX_LEN {
+                                        let warning = miette::miette!(
+                                            severity = Severity::Warning,
+                                            "certificate \"{}\" user-notice explicitText is {} characters, exceeding the RFC 5280 {}-character limit",
+                                            cert.name,
+                                            text.chars().count(),
+                                            EXPLICIT_TEXT_MAX_LEN
+                                        );
+                                        eprintln!("{:?}", warning);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(issuer_cert_name) = &cert.issuer_certificate {
+            // Presence was already checked above.
+            let issuer = doc
+                .certificates
+                .iter()
+                .find(|c| &c.name == issuer_cert_name)
+                .expect("issuer certificate name was validated above");
+            check_name_constraints(cert, issuer)?;
+            check_resource_coverage(cert, issuer)?;
+        }
     }
 
     Ok(doc)
 }
+
+/// Chains in a playground config are short by construction, but a cycle in `issuer_certificate`
+/// links would otherwise recurse forever; this bounds `verify_chain`'s depth the way path
+/// builders in relying-party verifiers bound their own search.
+const MAX_CHAIN_DEPTH: u32 = 32;
+
+/// Checks that `cert`'s signature verifies under `issuer`'s public key. `config::verify` only
+/// has the KDL-level config in hand; the DER certificates and key material it would need to do
+/// this itself are produced downstream by the certificate-building pipeline, so callers that
+/// have built those pass a verifier in here.
+pub type SignatureVerifier<'a> = dyn Fn(&Certificate, &Certificate) -> Result<()> + 'a;
+
+/// Walks each certificate in `doc` up through its `issuer_certificate` links to a self-signed
+/// root, checking the chain the way a relying party would: every signature verifies under its
+/// issuer's public key (via `verify_signature`), every non-leaf is a CA whose `path_len` isn't
+/// exceeded by the certificate's depth in the chain, every issuer asserts `keyCertSign`, and
+/// every certificate's validity window nests inside its issuer's.
+pub fn verify(doc: &Document, verify_signature: &SignatureVerifier) -> Result<()> {
+    let certs_by_name: std::collections::HashMap<&str, &Certificate> =
+        doc.certificates.iter().map(|c| (c.name.as_str(), c)).collect();
+
+    for cert in &doc.certificates {
+        verify_chain(cert, &certs_by_name, 0, verify_signature)?;
+    }
+
+    Ok(())
+}
+
+fn verify_chain(
+    cert: &Certificate,
+    certs_by_name: &std::collections::HashMap<&str, &Certificate>,
+    depth: u32,
+    verify_signature: &SignatureVerifier,
+) -> Result<()> {
+    let Some(issuer_name) = &cert.issuer_certificate else {
+        // Issued by an external entity (no `issuer_certificate` link) or self-signed; nothing
+        // further to walk.
+        return Ok(());
+    };
+
+    if depth >= MAX_CHAIN_DEPTH {
+        miette::bail!(
+            "certificate \"{}\" exceeds the maximum chain depth of {} issuer hops; check for a cycle in issuer-certificate links",
+            cert.name,
+            MAX_CHAIN_DEPTH
+        )
+    }
+
+    // Presence of the issuer certificate is already checked by `load_and_validate`.
+    let issuer = certs_by_name
+        .get(issuer_name.as_str())
+        .expect("issuer certificate name was validated by load_and_validate");
+
+    verify_signature(cert, issuer)?;
+
+    let basic_constraints = issuer.extensions.iter().find_map(|e| match e {
+        X509Extensions::BasicConstraints(bc) => Some(bc),
+        _ => None,
+    });
+    match basic_constraints {
+        None => miette::bail!(
+            "certificate \"{}\" is issued by \"{}\", which has no basicConstraints extension and so cannot act as a CA",
+            cert.name,
+            issuer.name
+        ),
+        Some(bc) if !bc.ca => miette::bail!(
+            "certificate \"{}\" is issued by \"{}\", which has basicConstraints ca=false",
+            cert.name,
+            issuer.name
+        ),
+        Some(bc) => {
+            if let Some(path_len) = bc.path_len {
+                if depth as u8 > path_len {
+                    miette::bail!(
+                        "issuer \"{}\" has {} intermediate certificate(s) below it in the chain through \"{}\", exceeding its basicConstraints path_len of {}",
+                        issuer.name,
+                        depth,
+                        cert.name,
+                        path_len
+                    )
+                }
+            }
+        }
+    }
+
+    let key_usage = issuer.extensions.iter().find_map(|e| match e {
+        X509Extensions::KeyUsage(ku) => Some(ku),
+        _ => None,
+    });
+    match key_usage {
+        None => miette::bail!(
+            "certificate \"{}\" is issued by \"{}\", which has no keyUsage extension asserting keyCertSign",
+            cert.name,
+            issuer.name
+        ),
+        Some(ku) if !ku.key_cert_sign => miette::bail!(
+            "certificate \"{}\" is issued by \"{}\", which does not assert keyCertSign in its keyUsage extension",
+            cert.name,
+            issuer.name
+        ),
+        Some(_) => {}
+    }
+
+    check_validity_nested(cert, issuer)?;
+
+    verify_chain(issuer, certs_by_name, depth + 1, verify_signature)
+}
+
+/// Parse an RFC 3339 timestamp (e.g. `2030-01-01T00:00:00Z`, or with a numeric `+HH:MM` offset)
+/// into seconds since the Unix epoch, so validity windows compare by calendar time rather than
+/// lexicographically. Rejects anything that isn't a well-formed, fixed-width RFC 3339 string
+/// instead of guessing at a looser format.
+fn parse_rfc3339(s: &str) -> Result<i64> {
+    let invalid = || miette::miette!("\"{}\" is not a valid RFC 3339 timestamp", s);
+    let bytes = s.as_bytes();
+    if bytes.len() < 20 {
+        return Err(invalid());
+    }
+
+    let digit = |i: usize| -> Result<i64> {
+        let b = *bytes.get(i).ok_or_else(invalid)?;
+        if b.is_ascii_digit() {
+            Ok((b - b'0') as i64)
+        } else {
+            Err(invalid())
+        }
+    };
+    let digits = |range: std::ops::Range<usize>| -> Result<i64> {
+        range.fold(Ok(0), |acc, i| Ok(acc? * 10 + digit(i)?))
+    };
+
+    if bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return Err(invalid());
+    }
+
+    let year = digits(0..4)?;
+    let month = digits(5..7)?;
+    let day = digits(8..10)?;
+    let hour = digits(11..13)?;
+    let minute = digits(14..16)?;
+    let second = digits(17..19)?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return Err(invalid());
+    }
+    let is_leap_year = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        2 if is_leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if day > days_in_month {
+        return Err(invalid());
+    }
+
+    let mut rest = &s[19..];
+    if let Some(frac) = rest.strip_prefix('.') {
+        let digit_count = frac.bytes().take_while(u8::is_ascii_digit).count();
+        if digit_count == 0 {
+            return Err(invalid());
+        }
+        rest = &frac[digit_count..];
+    }
+
+    let offset_seconds: i64 = if matches!(rest, "Z" | "z") {
+        0
+    } else {
+        let rb = rest.as_bytes();
+        let sign = match rb.first() {
+            Some(b'+') => 1,
+            Some(b'-') => -1,
+            _ => return Err(invalid()),
+        };
+        if rb.len() != 6 || rb[3] != b':' || !rb[1..3].iter().chain(&rb[4..6]).all(u8::is_ascii_digit) {
+            return Err(invalid());
+        }
+        let offset_hour = (rb[1] - b'0') as i64 * 10 + (rb[2] - b'0') as i64;
+        let offset_minute = (rb[4] - b'0') as i64 * 10 + (rb[5] - b'0') as i64;
+        if offset_hour > 23 || offset_minute > 59 {
+            return Err(invalid());
+        }
+        sign * (offset_hour * 3600 + offset_minute * 60)
+    };
+
+    let days = days_from_civil(year, month as u32, day as u32);
+    Ok(days * 86_400 + hour * 3600 + minute * 60 + second - offset_seconds)
+}
+
+/// Days since the Unix epoch for a proleptic-Gregorian civil date, via Howard Hinnant's
+/// `days_from_civil` algorithm.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let year_of_era = y - era * 400;
+    let month_prime = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_prime + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Require that `cert`'s validity window falls within `issuer`'s, comparing RFC 3339 timestamps
+/// by calendar time rather than lexicographically.
+fn check_validity_nested(cert: &Certificate, issuer: &Certificate) -> Result<()> {
+    // A missing `not-before` has no agreed default here (the certificate builder resolves it,
+    // not this module), so there's nothing honest to compare against; skip the lower-bound check
+    // rather than treating a missing value as the start of time.
+    if let (Some(cert_not_before), Some(issuer_not_before)) =
+        (cert.not_before.as_deref(), issuer.not_before.as_deref())
+    {
+        if parse_rfc3339(cert_not_before)? < parse_rfc3339(issuer_not_before)? {
+            miette::bail!(
+                "certificate \"{}\" is valid from \"{}\", before its issuer \"{}\" becomes valid at \"{}\"",
+                cert.name,
+                cert_not_before,
+                issuer.name,
+                issuer_not_before
+            )
+        }
+    }
+
+    if parse_rfc3339(&cert.not_after)? > parse_rfc3339(&issuer.not_after)? {
+        miette::bail!(
+            "certificate \"{}\" is valid until \"{}\", after its issuer \"{}\" expires at \"{}\"",
+            cert.name,
+            cert.not_after,
+            issuer.name,
+            issuer.not_after
+        )
+    }
+
+    Ok(())
+}
+
+/// OID of the `anyPolicy` special value defined by RFC 5280 §4.2.1.4.
+const ANY_POLICY_OID: &str = "2.5.29.32.0";
+
+/// A node surviving to the current depth of the RFC 5280 §6.1.3/§6.1.4 policy tree.
+struct PolicyNode {
+    valid_policy: String,
+    expected_policy_set: HashSet<String>,
+}
+
+fn certificate_policy_oid(policy: &CertificatePolicy) -> Result<String> {
+    Ok(PolicyInformation::try_from(policy)?.policy_identifier.to_string())
+}
+
+/// Walk `chain` (root first, leaf last) building the RFC 5280 policy tree: for each certificate's
+/// `CertificatePolicies`, graft a child under every current leaf whose `expected_policy_set`
+/// contains the policy OID (or anyPolicy), then prune leaves that gained no child. A certificate
+/// with no `CertificatePolicies` extension leaves the tree untouched. Returns the surviving
+/// leaves and, if the tree was pruned to nothing, the name of the certificate where that
+/// happened.
+fn build_policy_tree<'a>(chain: &[&'a Certificate]) -> Result<(Vec<PolicyNode>, Option<&'a str>)> {
+    let mut leaves = vec![PolicyNode {
+        valid_policy: ANY_POLICY_OID.to_string(),
+        expected_policy_set: [ANY_POLICY_OID.to_string()].into_iter().collect(),
+    }];
+    let mut diverged_at = None;
+
+    for cert in chain {
+        let Some(policies_ext) = cert.extensions.iter().find_map(|e| match e {
+            X509Extensions::CertificatePolicies(p) => Some(p),
+            _ => None,
+        }) else {
+            continue;
+        };
+
+        let mut oids = Vec::with_capacity(policies_ext.policies.len());
+        for policy in &policies_ext.policies {
+            let oid = certificate_policy_oid(policy)?;
+            if !oids.contains(&oid) {
+                oids.push(oid);
+            }
+        }
+
+        let mut new_leaves = Vec::new();
+        for oid in &oids {
+            let matching_parents = leaves
+                .iter()
+                .filter(|leaf| leaf.expected_policy_set.contains(oid.as_str()) || leaf.expected_policy_set.contains(ANY_POLICY_OID));
+
+            if oid == ANY_POLICY_OID {
+                // anyPolicy grafts one child per matching parent, inheriting that parent's
+                // expected_policy_set unchanged.
+                for parent in matching_parents {
+                    new_leaves.push(PolicyNode {
+                        valid_policy: ANY_POLICY_OID.to_string(),
+                        expected_policy_set: parent.expected_policy_set.clone(),
+                    });
+                }
+            } else if matching_parents.count() > 0 {
+                new_leaves.push(PolicyNode {
+                    valid_policy: oid.clone(),
+                    expected_policy_set: [oid.clone()].into_iter().collect(),
+                });
+            }
+        }
+
+        if new_leaves.is_empty() && !leaves.is_empty() && diverged_at.is_none() {
+            diverged_at = Some(cert.name.as_str());
+        }
+        leaves = new_leaves;
+    }
+
+    Ok((leaves, diverged_at))
+}
+
+fn root_to_leaf_chain<'a>(
+    leaf: &'a Certificate,
+    certs_by_name: &std::collections::HashMap<&str, &'a Certificate>,
+) -> Vec<&'a Certificate> {
+    let mut chain = vec![leaf];
+    let mut current = leaf;
+    while let Some(issuer_name) = &current.issuer_certificate {
+        let Some(issuer) = certs_by_name.get(issuer_name.as_str()) else {
+            break;
+        };
+        chain.push(issuer);
+        current = issuer;
+    }
+    chain.reverse();
+    chain
+}
+
+/// Reproduce the RFC 5280 §6.1.3/§6.1.4 policy-tree algorithm over every root-to-leaf chain in
+/// `doc`, so a broken DICE/OANA policy chain is caught before it's shipped. For each chain, the
+/// surviving leaf policies (anyPolicy leaves contribute their whole expected set) are intersected
+/// with `doc.initial_policy_set`; an empty intersection only fails the chain when some
+/// certificate in it set `require-explicit-policy` on a `PolicyConstraints` extension.
+pub fn validate_policy_tree(doc: &Document) -> Result<()> {
+    let certs_by_name: std::collections::HashMap<&str, &Certificate> =
+        doc.certificates.iter().map(|c| (c.name.as_str(), c)).collect();
+    let issued_from: HashSet<&str> = doc
+        .certificates
+        .iter()
+        .filter_map(|c| c.issuer_certificate.as_deref())
+        .collect();
+
+    let initial_policy_set: Option<HashSet<&str>> = if doc.initial_policy_set.is_empty()
+        || doc
+            .initial_policy_set
+            .iter()
+            .any(|p| p == "any-policy" || p == ANY_POLICY_OID)
+    {
+        None
+    } else {
+        Some(doc.initial_policy_set.iter().map(String::as_str).collect())
+    };
+
+    for leaf in doc
+        .certificates
+        .iter()
+        .filter(|c| !issued_from.contains(c.name.as_str()))
+    {
+        let chain = root_to_leaf_chain(leaf, &certs_by_name);
+
+        let explicit_policy_required = chain.iter().any(|cert| {
+            cert.extensions.iter().any(|e| {
+                matches!(
+                    e,
+                    X509Extensions::PolicyConstraints(pc) if pc.require_explicit_policy.is_some()
+                )
+            })
+        });
+
+        if !explicit_policy_required {
+            continue;
+        }
+
+        let (leaves, diverged_at) = build_policy_tree(&chain)?;
+
+        // Per RFC 5280 §6.1.5 step (g)(iii): a surviving anyPolicy node means the valid policy
+        // tree covers the entire initial-policy-set, not just the literal anyPolicy OID — it
+        // can't be intersected against `initial_policy_set` like a concrete policy OID.
+        let has_any_policy_leaf = leaves.iter().any(|n| n.valid_policy == ANY_POLICY_OID);
+
+        let surviving: HashSet<&str> = leaves
+            .iter()
+            .filter(|n| n.valid_policy != ANY_POLICY_OID)
+            .map(|n| n.valid_policy.as_str())
+            .collect();
+
+        let intersection_empty = if has_any_policy_leaf {
+            false
+        } else {
+            match &initial_policy_set {
+                None => surviving.is_empty(),
+                Some(initial) => surviving.is_disjoint(initial),
+            }
+        };
+
+        if intersection_empty {
+            let at = diverged_at
+                .map(|name| format!(" (policies diverged at certificate \"{name}\")"))
+                .unwrap_or_default();
+            miette::bail!(
+                "certificate \"{}\" requires an explicit policy, but its chain's valid policies share nothing with the initial-policy-set{}",
+                leaf.name,
+                at
+            )
+        }
+    }
+
+    Ok(())
+}
+
+/// An inclusive address/AS-number range, represented as a `u128` so IPv4, IPv6 and AS numbers can
+/// share the same merge/containment code; IPv4 addresses and AS numbers are simply small values
+/// within that range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct ResourceRange {
+    min: u128,
+    max: u128,
+}
+
+/// Sort and merge adjacent/overlapping ranges, as RFC 3779 §3.2.3.3 requires of a canonical
+/// resource set.
+fn canonicalize_ranges(mut ranges: Vec<ResourceRange>) -> Vec<ResourceRange> {
+    ranges.sort();
+    let mut merged: Vec<ResourceRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.min <= last.max.saturating_add(1) => {
+                last.max = last.max.max(range.max);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+fn ipv4_prefix_range(prefix: &str) -> Result<ResourceRange> {
+    let (addr, len) = parse_cidr(prefix)?;
+    let IpAddr::V4(addr) = addr else {
+        miette::bail!("ipv4 resource \"{}\" is not an IPv4 address", prefix)
+    };
+    let mask: u32 = if len == 0 { 0 } else { u32::MAX << (32 - len) };
+    let base = u32::from(addr) & mask;
+    Ok(ResourceRange {
+        min: base as u128,
+        max: (base | !mask) as u128,
+    })
+}
+
+fn ipv6_prefix_range(prefix: &str) -> Result<ResourceRange> {
+    let (addr, len) = parse_cidr(prefix)?;
+    let IpAddr::V6(addr) = addr else {
+        miette::bail!("ipv6 resource \"{}\" is not an IPv6 address", prefix)
+    };
+    let mask: u128 = if len == 0 { 0 } else { u128::MAX << (128 - len) };
+    let base = u128::from(addr) & mask;
+    Ok(ResourceRange {
+        min: base,
+        max: base | !mask,
+    })
+}
+
+fn ip_resource_entry_range(entry: &IpResourceEntry, is_v6: bool) -> Result<ResourceRange> {
+    match (&entry.prefix, &entry.min, &entry.max) {
+        (Some(prefix), None, None) => {
+            if is_v6 {
+                ipv6_prefix_range(prefix)
+            } else {
+                ipv4_prefix_range(prefix)
+            }
+        }
+        (None, Some(min), Some(max)) => {
+            let min: IpAddr = min
+                .parse()
+                .into_diagnostic()
+                .map_err(|e| miette::miette!("invalid min address \"{}\": {}", min, e))?;
+            let max: IpAddr = max
+                .parse()
+                .into_diagnostic()
+                .map_err(|e| miette::miette!("invalid max address \"{}\": {}", max, e))?;
+            Ok(match (min, max) {
+                (IpAddr::V4(min), IpAddr::V4(max)) => ResourceRange {
+                    min: u32::from(min) as u128,
+                    max: u32::from(max) as u128,
+                },
+                (IpAddr::V6(min), IpAddr::V6(max)) => ResourceRange {
+                    min: u128::from(min),
+                    max: u128::from(max),
+                },
+                _ => miette::bail!("min/max addresses must be the same address family"),
+            })
+        }
+        _ => miette::bail!("an ip resource entry must have either a CIDR prefix or both min and max"),
+    }
+}
+
+fn ip_resource_ranges(entries: &[IpResourceEntry], is_v6: bool) -> Result<Vec<ResourceRange>> {
+    let ranges = entries
+        .iter()
+        .map(|e| ip_resource_entry_range(e, is_v6))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(canonicalize_ranges(ranges))
+}
+
+fn as_resource_entry_range(entry: &AsResourceEntry) -> Result<ResourceRange> {
+    match (entry.value, entry.min, entry.max) {
+        (Some(value), None, None) => Ok(ResourceRange {
+            min: value as u128,
+            max: value as u128,
+        }),
+        (None, Some(min), Some(max)) => Ok(ResourceRange {
+            min: min as u128,
+            max: max as u128,
+        }),
+        _ => miette::bail!("an asn resource entry must have either a single value or both min and max"),
+    }
+}
+
+fn as_resource_ranges(entries: &[AsResourceEntry]) -> Result<Vec<ResourceRange>> {
+    let ranges = entries
+        .iter()
+        .map(as_resource_entry_range)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(canonicalize_ranges(ranges))
+}
+
+/// Does every range in `child` fall within some single range of `parent`? `parent` must already
+/// be canonicalized (adjacent/overlapping blocks merged): a child range spanning two unmerged
+/// parent blocks is otherwise rejected.
+fn ranges_covered_by(child: &[ResourceRange], parent: &[ResourceRange]) -> bool {
+    child.iter().all(|c| {
+        parent
+            .iter()
+            .any(|p| p.min <= c.min && c.max <= p.max)
+    })
+}
+
+/// Check that `cert`'s RFC 3779 IP/AS resources are covered by `issuer`'s. A certificate that
+/// declares no resources, or that inherits them, trivially passes; otherwise every explicit
+/// block must fall within a block the issuer holds. A resource type for which the issuer's own
+/// extension inherits is not checked, since the issuer's effective set is not resolved here.
+fn check_resource_coverage(cert: &Certificate, issuer: &Certificate) -> Result<()> {
+    if let Some(X509Extensions::IpResources(child_ip)) = cert
+        .extensions
+        .iter()
+        .find(|e| matches!(e, X509Extensions::IpResources(_)))
+    {
+        if !child_ip.inherit {
+            let issuer_ip = issuer.extensions.iter().find_map(|e| match e {
+                X509Extensions::IpResources(ip) => Some(ip),
+                _ => None,
+            });
+
+            let child_v4 = ip_resource_ranges(&child_ip.ipv4, false)?;
+            let child_v6 = ip_resource_ranges(&child_ip.ipv6, true)?;
+
+            let issuer_ranges = match issuer_ip {
+                // The issuer itself inherits; we can't resolve its effective resources
+                // without walking further up the chain, so we don't second-guess it here.
+                Some(ip) if ip.inherit => None,
+                Some(ip) => Some((ip_resource_ranges(&ip.ipv4, false)?, ip_resource_ranges(&ip.ipv6, true)?)),
+                None => Some((Vec::new(), Vec::new())),
+            };
+
+            if let Some((issuer_v4, issuer_v6)) = issuer_ranges {
+                if !ranges_covered_by(&child_v4, &issuer_v4) || !ranges_covered_by(&child_v6, &issuer_v6) {
+                    miette::bail!(
+                        "certificate \"{}\" claims IP resources not held by its issuer \"{}\"",
+                        cert.name,
+                        issuer.name
+                    )
+                }
+            }
+        }
+    }
+
+    if let Some(X509Extensions::AsResources(child_as)) = cert
+        .extensions
+        .iter()
+        .find(|e| matches!(e, X509Extensions::AsResources(_)))
+    {
+        if !child_as.inherit {
+            let issuer_as = issuer.extensions.iter().find_map(|e| match e {
+                X509Extensions::AsResources(asres) => Some(asres),
+                _ => None,
+            });
+
+            let child_ranges = as_resource_ranges(&child_as.asn)?;
+
+            let issuer_ranges = match issuer_as {
+                Some(asres) if asres.inherit => return Ok(()),
+                Some(asres) => as_resource_ranges(&asres.asn)?,
+                None => Vec::new(),
+            };
+
+            if !ranges_covered_by(&child_ranges, &issuer_ranges) {
+                miette::bail!(
+                    "certificate \"{}\" claims AS numbers not held by its issuer \"{}\"",
+                    cert.name,
+                    issuer.name
+                )
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Append a DER tag-length-value header followed by `content` to `out`.
+fn der_tlv(out: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    out.push(tag);
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let first_nonzero = len_bytes.iter().position(|&b| b != 0).unwrap_or(len_bytes.len() - 1);
+        let significant = &len_bytes[first_nonzero..];
+        out.push(0x80 | significant.len() as u8);
+        out.extend_from_slice(significant);
+    }
+    out.extend_from_slice(content);
+}
+
+/// Encode an RFC 3779 `IPAddress` (`BIT STRING`) truncated to `prefix_len` bits of `octets`, with
+/// the leading content octet giving the number of unused bits in the last byte kept, per §2.1.1.
+/// A `10.0.0.0/8` block is 1 content byte (`10`) plus the unused-bits octet, not the full 4 bytes.
+fn der_ip_address_bit_string(octets: &[u8], prefix_len: u8) -> Vec<u8> {
+    let used_bytes = prefix_len.div_ceil(8) as usize;
+    let unused_bits = if prefix_len % 8 == 0 { 0 } else { 8 - (prefix_len % 8) };
+
+    let mut content = vec![unused_bits];
+    content.extend_from_slice(&octets[..used_bytes]);
+    if unused_bits != 0 {
+        if let Some(last) = content.last_mut() {
+            *last &= 0xffu8 << unused_bits;
+        }
+    }
+    let mut out = Vec::new();
+    der_tlv(&mut out, 0x03, &content);
+    out
+}
+
+fn u128_to_octets(value: u128, is_v6: bool) -> Vec<u8> {
+    if is_v6 {
+        value.to_be_bytes().to_vec()
+    } else {
+        (value as u32).to_be_bytes().to_vec()
+    }
+}
+
+/// The number of significant bits in `value`'s canonical RFC 3779 §2.1.2 `min` encoding: trailing
+/// zero bits are dropped, so an all-zero value (the bottom of the address space) is 0 bits.
+fn min_significant_bits(value: u128, bits: u8) -> u8 {
+    if value == 0 {
+        0
+    } else {
+        bits - (value.trailing_zeros().min(bits as u32) as u8)
+    }
+}
+
+/// The number of significant bits in `value`'s canonical RFC 3779 §2.1.2 `max` encoding: trailing
+/// one bits are dropped, so an all-ones value (the top of the address space) is 0 bits.
+fn max_significant_bits(value: u128, bits: u8) -> u8 {
+    let trailing_ones = (!value).trailing_zeros().min(bits as u32);
+    bits - trailing_ones as u8
+}
+
+/// Encode one `IPAddressOrRange`: a single CIDR-aligned block as an `IPAddress` prefix, or an
+/// arbitrary range as an `IPAddressRange` of two canonically-trimmed `IPAddress` bit strings
+/// (`min` drops trailing zero bits, `max` drops trailing one bits, per RFC 3779 §2.1.2).
+fn der_ip_address_or_range(range: &ResourceRange, is_v6: bool) -> Vec<u8> {
+    let bits: u8 = if is_v6 { 128 } else { 32 };
+    let span = range.max - range.min;
+    let significant_bits = (u128::BITS - span.leading_zeros()).min(bits as u32) as u8;
+    let prefix_len = bits - significant_bits;
+    let shift: u32 = (bits - prefix_len) as u32;
+
+    let full_mask: u128 = if shift == 0 {
+        0
+    } else if shift >= u128::BITS {
+        u128::MAX
+    } else {
+        (1u128 << shift) - 1
+    };
+    let base = if shift >= u128::BITS { 0 } else { (range.min >> shift) << shift };
+    let is_cidr_aligned = range.min == base && span == full_mask;
+
+    if is_cidr_aligned {
+        der_ip_address_bit_string(&u128_to_octets(range.min, is_v6), prefix_len)
+    } else {
+        let min_prefix_len = min_significant_bits(range.min, bits);
+        let max_prefix_len = max_significant_bits(range.max, bits);
+        let mut content = Vec::new();
+        content.extend_from_slice(&der_ip_address_bit_string(
+            &u128_to_octets(range.min, is_v6),
+            min_prefix_len,
+        ));
+        content.extend_from_slice(&der_ip_address_bit_string(
+            &u128_to_octets(range.max, is_v6),
+            max_prefix_len,
+        ));
+        let mut out = Vec::new();
+        der_tlv(&mut out, 0x30, &content);
+        out
+    }
+}
+
+/// Encode one `IPAddressFamily`: an `addressFamily` AFI octet string followed by either an
+/// `inherit` NULL or a `SEQUENCE OF IPAddressOrRange`.
+fn der_ip_address_family(afi: u16, ranges: &[ResourceRange], is_v6: bool, inherit: bool) -> Vec<u8> {
+    let mut address_family_bytes = Vec::new();
+    der_tlv(&mut address_family_bytes, 0x04, &afi.to_be_bytes());
+
+    let mut choice = Vec::new();
+    if inherit {
+        der_tlv(&mut choice, 0x05, &[]);
+    } else {
+        let mut entries = Vec::new();
+        for range in ranges {
+            entries.extend_from_slice(&der_ip_address_or_range(range, is_v6));
+        }
+        der_tlv(&mut choice, 0x30, &entries);
+    }
+
+    let mut content = address_family_bytes;
+    content.extend_from_slice(&choice);
+
+    let mut out = Vec::new();
+    der_tlv(&mut out, 0x30, &content);
+    out
+}
+
+impl TryFrom<&IpResourcesExtension> for x509_cert::ext::Extension {
+    type Error = miette::Error;
+
+    /// RFC 3779 §2.2.3 `id-pe-ipAddrBlocks`. `x509_cert` does not ship RFC 3779 types, so this
+    /// extension is DER-encoded by hand and wrapped in the generic `Extension` envelope.
+    fn try_from(value: &IpResourcesExtension) -> Result<Self> {
+        let v4_ranges = ip_resource_ranges(&value.ipv4, false)?;
+        let v6_ranges = ip_resource_ranges(&value.ipv6, true)?;
+
+        let mut families = Vec::new();
+        if value.inherit || !v4_ranges.is_empty() {
+            families.extend_from_slice(&der_ip_address_family(1, &v4_ranges, false, value.inherit));
+        }
+        if value.inherit || !v6_ranges.is_empty() {
+            families.extend_from_slice(&der_ip_address_family(2, &v6_ranges, true, value.inherit));
+        }
+
+        let mut ip_addr_blocks = Vec::new();
+        der_tlv(&mut ip_addr_blocks, 0x30, &families);
+
+        Ok(x509_cert::ext::Extension {
+            extn_id: ObjectIdentifier::new("1.3.6.1.5.5.7.1.7").into_diagnostic()?,
+            critical: value.critical,
+            extn_value: OctetString::new(ip_addr_blocks).into_diagnostic()?,
+        })
+    }
+}
+
+/// Minimal-length big-endian DER `INTEGER` content octets for `value`: strip leading zero bytes,
+/// keeping exactly one `0x00` when the remaining high bit would otherwise read as negative.
+fn der_integer_content(value: u32) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let Some(mut first_nonzero) = bytes.iter().position(|&b| b != 0) else {
+        return vec![0u8];
+    };
+    if bytes[first_nonzero] & 0x80 != 0 {
+        if first_nonzero == 0 {
+            let mut content = vec![0u8];
+            content.extend_from_slice(&bytes);
+            return content;
+        }
+        first_nonzero -= 1;
+    }
+    bytes[first_nonzero..].to_vec()
+}
+
+fn der_as_id_or_range(range: &ResourceRange) -> Vec<u8> {
+    if range.min == range.max {
+        let mut out = Vec::new();
+        der_tlv(&mut out, 0x02, &der_integer_content(range.min as u32));
+        out
+    } else {
+        let mut content = Vec::new();
+        der_tlv(&mut content, 0x02, &der_integer_content(range.min as u32));
+        der_tlv(&mut content, 0x02, &der_integer_content(range.max as u32));
+        let mut out = Vec::new();
+        der_tlv(&mut out, 0x30, &content);
+        out
+    }
+}
+
+impl TryFrom<&AsResourcesExtension> for x509_cert::ext::Extension {
+    type Error = miette::Error;
+
+    /// RFC 3779 §3.2.3 `id-pe-autonomousSysIds`, populating only the `asnum` `[0] EXPLICIT`
+    /// field (the `rdi` field is never produced by `pki-playground`).
+    fn try_from(value: &AsResourcesExtension) -> Result<Self> {
+        let ranges = as_resource_ranges(&value.asn)?;
+
+        let mut as_id_choice = Vec::new();
+        if value.inherit {
+            der_tlv(&mut as_id_choice, 0x05, &[]);
+        } else {
+            let mut entries = Vec::new();
+            for range in &ranges {
+                entries.extend_from_slice(&der_as_id_or_range(range));
+            }
+            der_tlv(&mut as_id_choice, 0x30, &entries);
+        }
+
+        // [0] EXPLICIT wraps the ASIdentifierChoice above.
+        let mut asnum = Vec::new();
+        der_tlv(&mut asnum, 0xa0, &as_id_choice);
+
+        let mut as_identifiers = Vec::new();
+        der_tlv(&mut as_identifiers, 0x30, &asnum);
+
+        Ok(x509_cert::ext::Extension {
+            extn_id: ObjectIdentifier::new("1.3.6.1.5.5.7.1.8").into_diagnostic()?,
+            critical: value.critical,
+            extn_value: OctetString::new(as_identifiers).into_diagnostic()?,
+        })
+    }
+}